@@ -4,31 +4,266 @@
 
 //! FOR_RELEASE: Docs
 //!
-//! FOR_RELEASE: Currently, the macro requires that you chomium::import! the
+//! By default, the macro requires that you chromium::import! the
 //! mojom_parser crate, so ensure that all the type names (MojomParse, MojomValue,
-//! etc.) are in scope. To remove this restriction, we could instead qualify each
-//! of them with the absolute path (something like `mojom_parser::MojomValue`).
-//! Unfortunately, chromium mangles the names of the crates (hence the need for
-//! the chromium_import! macro), so the absolute path isn't easy to write, and
-//! we'd need to make sure we stay up to date if the mangling changes.
+//! etc.) are in scope. If that's not possible -- e.g. the type is defined in a
+//! module that can't or doesn't want to import the parser crate globally --
+//! add a `#[mojom(crate = path::to::mojom_parser)]` attribute on the type and
+//! the generated code will qualify every reference with that path instead.
+//!
+//! Fields can carry a `#[mojom(...)]` attribute to adjust how they're
+//! (de)serialized: `rename = "wireName"` uses a different name on the wire
+//! than the Rust field identifier, `skip` leaves the field out of the wire
+//! representation entirely (it's reconstructed via `Default`), and `default`
+//! falls back to `Default::default()` if the wire value doesn't have the
+//! field. `optional` (requiring the field to be `Option<T>`, or also marked
+//! `default`) and `min_version = N` model Mojom's `[MinVersion]` schema
+//! evolution: an absent field resolves to `None`/`Default::default()`
+//! instead of an error, so one Rust type can read both old and new wire
+//! payloads.
 
 use quote::quote;
-use syn::{parse_macro_input, DeriveInput};
+use syn::{parse_macro_input, Data, DeriveInput};
 
-#[proc_macro_derive(MojomParse)]
+#[proc_macro_derive(MojomParse, attributes(mojom))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let name = input.ident;
-
-    let struct_fields = match input.data {
-        syn::Data::Struct(syn::DataStruct { fields, .. }) => match fields {
-            syn::Fields::Named(syn::FieldsNamed { named, .. }) => named,
-            _ => todo!("Gotta have named fields, give an error message"),
-        },
-        _ => todo!("Only structs supported at the moment"),
+
+    let container_attrs = match parse_container_attrs(&input.attrs) {
+        Ok(attrs) => attrs,
+        Err(err) => return proc_macro::TokenStream::from(err.to_compile_error()),
     };
+    let paths = MojomPaths::new(container_attrs.crate_path.as_ref());
+
+    let quoted = match &input.data {
+        Data::Struct(data_struct) => derive_struct(&input, data_struct, &paths),
+        Data::Enum(data_enum) => derive_enum(&input, data_enum, &paths),
+        Data::Union(_) => {
+            syn::Error::new_spanned(&input.ident, "MojomParse does not support Rust unions")
+                .to_compile_error()
+        }
+    };
+
+    // Excellent for debugging, prints out the entire generated code
+    // println!("{}", &quoted);
+    return proc_macro::TokenStream::from(quoted);
+}
+
+// The parsed form of the derived type's own `#[mojom(...)]` attribute (as
+// opposed to `FieldAttrs`, which is per-field).
+struct ContainerAttrs {
+    // `#[mojom(crate = path::to::mojom_parser)]`: qualify every generated
+    // reference to MojomParse/MojomType/MojomValue with this path, instead of
+    // assuming they're bare names brought into scope by `chromium::import!`.
+    crate_path: Option<syn::Path>,
+}
+
+fn parse_container_attrs(attrs: &[syn::Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut result = ContainerAttrs { crate_path: None };
+    for attr in attrs {
+        if !attr.path().is_ident("mojom") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                result.crate_path = Some(meta.value()?.parse::<syn::Path>()?);
+            } else {
+                return Err(meta.error("unrecognized #[mojom(...)] container attribute"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(result)
+}
+
+// The (possibly crate-path-qualified) token streams to use in generated code
+// for each of the three mojom_parser types, so the rest of this file doesn't
+// need to care whether `#[mojom(crate = ..)]` was given.
+struct MojomPaths {
+    mojom_parse: proc_macro2::TokenStream,
+    mojom_type: proc_macro2::TokenStream,
+    mojom_value: proc_macro2::TokenStream,
+}
+
+impl MojomPaths {
+    fn new(crate_path: Option<&syn::Path>) -> Self {
+        match crate_path {
+            Some(path) => Self {
+                mojom_parse: quote! { #path::MojomParse },
+                mojom_type: quote! { #path::MojomType },
+                mojom_value: quote! { #path::MojomValue },
+            },
+            None => Self {
+                mojom_parse: quote! { MojomParse },
+                mojom_type: quote! { MojomType },
+                mojom_value: quote! { MojomValue },
+            },
+        }
+    }
+}
+
+// The parsed form of a single field's `#[mojom(...)]` attribute.
+struct FieldAttrs {
+    // `#[mojom(rename = "wireName")]`: the name used on the wire, if it
+    // differs from the Rust field identifier.
+    rename: Option<String>,
+    // `#[mojom(skip)]`: omit this field from `mojom_type()`/`From`/`TryFrom`
+    // entirely; it's reconstructed via `Default` on the way back in.
+    skip: bool,
+    // `#[mojom(default)]`: if the wire value doesn't have this field,
+    // fall back to `Default::default()` instead of erroring out.
+    default: bool,
+    // `#[mojom(optional)]`: this field was added in a later Mojom schema
+    // version and may be absent from an older sender's wire value. The field
+    // must be `Option<T>` (absent maps to `None`) or also marked `default`.
+    optional: bool,
+    // `#[mojom(min_version = N)]`: records the Mojom `[MinVersion]` this
+    // field was introduced at, for `mojom_type()` to report. Only valid
+    // alongside `optional`.
+    min_version: Option<syn::LitInt>,
+}
+
+fn parse_field_attrs(field: &syn::Field) -> syn::Result<FieldAttrs> {
+    let mut attrs =
+        FieldAttrs { rename: None, skip: false, default: false, optional: false, min_version: None };
+    for attr in &field.attrs {
+        if !attr.path().is_ident("mojom") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse::<syn::LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                attrs.skip = true;
+            } else if meta.path.is_ident("default") {
+                attrs.default = true;
+            } else if meta.path.is_ident("optional") {
+                attrs.optional = true;
+            } else if meta.path.is_ident("min_version") {
+                attrs.min_version = Some(meta.value()?.parse::<syn::LitInt>()?);
+            } else {
+                return Err(meta.error("unrecognized #[mojom(...)] field attribute"));
+            }
+            Ok(())
+        })?;
+    }
 
-    let num_fields = struct_fields.len();
+    if attrs.min_version.is_some() && !attrs.optional {
+        return Err(syn::Error::new_spanned(
+            field,
+            "#[mojom(min_version = ..)] requires the field to also be #[mojom(optional)]",
+        ));
+    }
+    // A `skip`ped field never touches the wire, so `optional`/`min_version`
+    // have nothing to say about it; don't apply the Option<T>-or-default
+    // requirement below to a combination that's a no-op anyway.
+    if attrs.optional && !attrs.skip && !attrs.default && option_inner_type(&field.ty).is_none() {
+        return Err(syn::Error::new_spanned(
+            field,
+            "#[mojom(optional)] requires the field to be Option<T>, or to also be #[mojom(default)]",
+        ));
+    }
+
+    Ok(attrs)
+}
+
+// If `ty` is `Option<T>`, returns `T`; otherwise `None`. This is a syntactic
+// check (it'd also "match" some unrelated type a user named `Option`), the
+// same tradeoff serde's derive makes for the same reason: there's no way to
+// resolve type aliases inside a proc macro.
+fn option_inner_type(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(type_path) = ty else { return None };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+// `input.generics` as-is just carries the type parameters themselves (e.g.
+// `<T>`); it doesn't know anything about our derived impls needing `T` itself
+// to be (de)serializable. This adds a `T: MojomParse` (and friends) bound for
+// every type parameter, the same way coi-derive and avro_derive do, so that
+// e.g. `#[derive(MojomParse)] struct Wrapper<T> { inner: T }` produces an impl
+// that actually compiles.
+fn add_mojom_parse_bounds(generics: &syn::Generics, paths: &MojomPaths) -> syn::Generics {
+    let mut generics = generics.clone();
+    let mojom_parse = &paths.mojom_parse;
+    let mojom_value = &paths.mojom_value;
+    let type_params: Vec<syn::Ident> = generics.type_params().map(|param| param.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for ty in type_params {
+        where_clause.predicates.push(syn::parse_quote! { #ty: #mojom_parse });
+        where_clause.predicates.push(syn::parse_quote! { #mojom_value: From<#ty> });
+        where_clause.predicates.push(syn::parse_quote! { #ty: TryFrom<#mojom_value, Error = ::anyhow::Error> });
+    }
+    generics
+}
+
+fn derive_struct(
+    input: &DeriveInput,
+    data_struct: &syn::DataStruct,
+    paths: &MojomPaths,
+) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+
+    let struct_fields = match &data_struct.fields {
+        syn::Fields::Named(syn::FieldsNamed { named, .. }) => named,
+        _ => {
+            return syn::Error::new_spanned(
+                name,
+                "MojomParse can only be derived on structs with named fields",
+            )
+            .to_compile_error();
+        }
+    };
+
+    // Pair each field up with its parsed `#[mojom(...)]` attribute, bailing
+    // out with a span-attached error on the first field that fails to parse.
+    let mut fields_with_attrs: Vec<(&syn::Field, FieldAttrs)> = Vec::new();
+    for field in struct_fields.iter() {
+        match parse_field_attrs(field) {
+            Ok(attrs) => fields_with_attrs.push((field, attrs)),
+            Err(err) => return err.to_compile_error(),
+        }
+    }
+
+    // Two fields that resolve to the same effective wire name (via `rename`)
+    // would serialize fine but could never deserialize, since the `TryFrom`
+    // generated below rejects duplicate keys -- catch the collision here instead, with
+    // a diagnostic that actually points at the rename. `skip`ped fields never
+    // touch the wire, so they can't collide with anything.
+    let mut seen_wire_names: std::collections::HashMap<String, &syn::Field> = std::collections::HashMap::new();
+    for (field, attrs) in fields_with_attrs.iter().filter(|(_, attrs)| !attrs.skip) {
+        let wire_name = attrs.rename.clone().unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+        if let Some(other_field) = seen_wire_names.insert(wire_name.clone(), field) {
+            return syn::Error::new_spanned(
+                field,
+                format!(
+                    "field {:?} has the same wire name {:?} as field {:?}; give one a \
+                     #[mojom(rename = ..)] that doesn't collide",
+                    field.ident.as_ref().unwrap().to_string(),
+                    wire_name,
+                    other_field.ident.as_ref().unwrap().to_string(),
+                ),
+            )
+            .to_compile_error();
+        }
+    }
+
+    // Computed up front since `type_name::<#name #ty_generics>()` (used in the
+    // generated error messages below) needs concrete-looking generic
+    // arguments in scope -- `type_name::<#name>()` alone doesn't compile for
+    // a generic struct.
+    let generics = add_mojom_parse_bounds(&input.generics, paths);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mojom_parse = &paths.mojom_parse;
+    let mojom_type = &paths.mojom_type;
+    let mojom_value = &paths.mojom_value;
 
     // As far as I know, quote can only iterate over vectors of things that can
     // be directly converted to tokens. Notably, this means they have to be
@@ -36,89 +271,299 @@ pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     // loop, we first have to combine each pair of names and values into a single
     // token stream, and then can we iterate over that in the quote.
 
-    // The names of the fields in the struct.
-    let field_idents: Vec<&syn::Ident> =
-        struct_fields.iter().map(|field| field.ident.as_ref().unwrap()).collect();
-
-    // A bunch of entries for a MojomType::Struct
-    let mojom_type_fields: Vec<proc_macro2::TokenStream> = struct_fields
+    // A bunch of entries for a MojomType::Struct. Skipped fields don't appear
+    // on the wire at all, so they're left out here. The third element of each
+    // tuple is the `[MinVersion]` the field was introduced at, if any, so
+    // schema-evolution tooling can tell how old a sender may omit it.
+    let mojom_type_fields: Vec<proc_macro2::TokenStream> = fields_with_attrs
         .iter()
-        .map(|field| {
-            let ty = &field.ty;
-            let name = field.ident.as_ref().unwrap().to_string();
-            quote! { (#name.to_string(), #ty::mojom_type()) }
+        .filter(|(_, attrs)| !attrs.skip)
+        .map(|(field, attrs)| {
+            let ty = if attrs.optional {
+                option_inner_type(&field.ty).unwrap_or(&field.ty)
+            } else {
+                &field.ty
+            };
+            let wire_name = attrs.rename.clone().unwrap_or_else(|| field.ident.as_ref().unwrap().to_string());
+            let min_version = match &attrs.min_version {
+                Some(version) => quote! { Some(#version) },
+                None => quote! { None },
+            };
+            quote! { (#wire_name.to_string(), <#ty as #mojom_parse>::mojom_type(), #min_version) }
         })
         .collect();
 
-    // A bunch of entries for a MojomValue::Struct
-    let to_mojom_value_fields: Vec<proc_macro2::TokenStream> = struct_fields
+    // Statements that push a (name, value) entry for a MojomValue::Struct.
+    // Skipped fields don't get a statement at all. An `optional` field whose
+    // value is `None` doesn't get a statement run either, so the field is
+    // simply absent from the wire value rather than sent as some null marker
+    // -- this is how Mojom's own `[MinVersion]` fields behave.
+    let to_mojom_value_fields: Vec<proc_macro2::TokenStream> = fields_with_attrs
         .iter()
-        .map(|field| {
-            let name = field.ident.as_ref().unwrap();
-            let name_str = name.to_string();
-            quote! { (#name_str.to_string(), value.#name.into()) }
+        .filter(|(_, attrs)| !attrs.skip)
+        .map(|(field, attrs)| {
+            let ident = field.ident.as_ref().unwrap();
+            let wire_name = attrs.rename.clone().unwrap_or_else(|| ident.to_string());
+            if attrs.optional && option_inner_type(&field.ty).is_some() {
+                quote! {
+                    if let Some(inner) = value.#ident {
+                        fields.push((#wire_name.to_string(), inner.into()));
+                    }
+                }
+            } else {
+                quote! { fields.push((#wire_name.to_string(), value.#ident.into())); }
+            }
         })
         .collect();
 
-    // The body of a struct value, converting each field from a MojomValue with
-    // the same name as the field.
-    let from_mojom_value_fields: Vec<proc_macro2::TokenStream> = struct_fields
+    // The body of a struct value. Each field is looked up in the incoming
+    // MojomValue::Struct by its (possibly renamed) wire name, rather than by
+    // position, since `skip`, `default` and `optional` fields may not be
+    // present at all.
+    let from_mojom_value_fields: Vec<proc_macro2::TokenStream> = fields_with_attrs
         .iter()
-        .map(|field| {
-            let name = field.ident.as_ref().unwrap();
-            quote! { #name: #name.try_into()? }
+        .map(|(field, attrs)| {
+            let ident = field.ident.as_ref().unwrap();
+            if attrs.skip {
+                return quote! { #ident: ::std::default::Default::default() };
+            }
+            let wire_name = attrs.rename.clone().unwrap_or_else(|| ident.to_string());
+            if attrs.optional && option_inner_type(&field.ty).is_some() {
+                quote! {
+                    #ident: match fields.remove(#wire_name) {
+                        Some(field_value) => Some(field_value.try_into()?),
+                        None => None,
+                    }
+                }
+            } else if attrs.default {
+                quote! {
+                    #ident: match fields.remove(#wire_name) {
+                        Some(field_value) => field_value.try_into()?,
+                        None => ::std::default::Default::default(),
+                    }
+                }
+            } else {
+                quote! {
+                    #ident: fields.remove(#wire_name)
+                        .ok_or_else(|| ::anyhow::anyhow!(
+                            "Missing field {:?} constructing a value of type {}",
+                            #wire_name,
+                            std::any::type_name::<#name #ty_generics>(),
+                        ))?
+                        .try_into()?
+                }
+            }
         })
         .collect();
 
-    let quoted = quote! {
-        impl MojomParse for #name {
-            fn mojom_type() -> MojomType {
-                let fields : Vec<(String, MojomType)> = vec![
+    quote! {
+        impl #impl_generics #mojom_parse for #name #ty_generics #where_clause {
+            fn mojom_type() -> #mojom_type {
+                let fields : Vec<(String, #mojom_type, Option<u32>)> = vec![
                     #(#mojom_type_fields),*
                 ];
-                MojomType::Struct { fields }
+                #mojom_type::Struct { fields }
+            }
+        }
+
+        impl #impl_generics From<#name #ty_generics> for #mojom_value #where_clause {
+            fn from(value: #name #ty_generics) -> #mojom_value {
+                let mut fields : Vec<(String, #mojom_value)> = Vec::new();
+                #(#to_mojom_value_fields)*
+                #mojom_value::Struct ( fields )
             }
         }
 
-        impl From<#name> for MojomValue {
-            fn from(value: #name) -> MojomValue {
-                let fields : Vec<(String, MojomValue)> = vec![
-                    #(#to_mojom_value_fields),*
+        impl #impl_generics TryFrom<#mojom_value> for #name #ty_generics #where_clause {
+            type Error = ::anyhow::Error;
+
+            fn try_from(value : #mojom_value) -> ::anyhow::Result<Self> {
+                let #mojom_value::Struct(field_list) = value else {
+                    ::anyhow::bail!(
+                        "Cannot construct a value of type {} from non-struct MojomValue",
+                        std::any::type_name::<#name #ty_generics>(),
+                    );
+                };
+
+                // Build a name -> value map so fields can be looked up by their
+                // (possibly renamed) wire name rather than by position; this is
+                // also what lets `skip`/`default` fields be absent. A duplicate
+                // key means the sender's MojomValue::Struct is malformed, so we
+                // reject it rather than silently keeping the last one.
+                let mut fields : std::collections::HashMap<String, #mojom_value> =
+                    std::collections::HashMap::with_capacity(field_list.len());
+                for (field_name, field_value) in field_list {
+                    if fields.insert(field_name.clone(), field_value).is_some() {
+                        ::anyhow::bail!(
+                            "Duplicate field {:?} constructing a value of type {}",
+                            field_name,
+                            std::any::type_name::<#name #ty_generics>(),
+                        );
+                    }
+                }
+
+                // Any entries left over in `fields` after all of ours are
+                // `remove`d below are tolerated: they're either fields this
+                // version of the struct doesn't know about yet (forward
+                // compatibility with a newer wire schema) or a `skip`ped field
+                // that the sender serialized anyway.
+                Ok(Self {
+                    #(#from_mojom_value_fields),*
+                })
+            }
+        }
+    }
+}
+
+// Mojom enums are plain C-like enums (a name plus an integer discriminant).
+// Mojom unions are modeled as a Rust enum whose variants each carry exactly
+// one field -- the payload for that union member. We don't allow mixing the
+// two styles in a single Rust enum, since it's not clear what that would even
+// mean on the wire.
+fn derive_enum(
+    input: &DeriveInput,
+    data_enum: &syn::DataEnum,
+    paths: &MojomPaths,
+) -> proc_macro2::TokenStream {
+    let name = &input.ident;
+
+    let all_unit = data_enum.variants.iter().all(|variant| matches!(variant.fields, syn::Fields::Unit));
+    let all_single_field = data_enum.variants.iter().all(|variant| match &variant.fields {
+        syn::Fields::Unnamed(fields) => fields.unnamed.len() == 1,
+        _ => false,
+    });
+
+    if all_unit {
+        derive_c_like_enum(name, data_enum, paths)
+    } else if all_single_field {
+        derive_union_enum(name, data_enum, paths)
+    } else {
+        syn::Error::new_spanned(
+            &data_enum.variants,
+            "MojomParse requires an enum's variants to be either all unit variants \
+             (a Mojom enum) or all single-field variants (a Mojom union); mixing the \
+             two is not supported",
+        )
+        .to_compile_error()
+    }
+}
+
+fn derive_c_like_enum(
+    name: &syn::Ident,
+    data_enum: &syn::DataEnum,
+    paths: &MojomPaths,
+) -> proc_macro2::TokenStream {
+    let variant_idents: Vec<&syn::Ident> =
+        data_enum.variants.iter().map(|variant| &variant.ident).collect();
+    let variant_names: Vec<String> =
+        variant_idents.iter().map(|ident| ident.to_string()).collect();
+    let MojomPaths { mojom_parse, mojom_type, mojom_value } = paths;
+
+    // We let Rust compute the discriminant for us (via `as i64`) rather than
+    // re-implementing its "explicit, or one more than the previous" rule
+    // ourselves, so `Foo = 3` is respected automatically.
+    quote! {
+        impl #mojom_parse for #name {
+            fn mojom_type() -> #mojom_type {
+                let variants : Vec<(String, i64)> = vec![
+                    #((#variant_names.to_string(), #name::#variant_idents as i64)),*
                 ];
-                MojomValue::Struct ( fields )
+                #mojom_type::Enum { variants }
             }
         }
 
-        impl TryFrom<MojomValue> for #name {
+        impl From<#name> for #mojom_value {
+            fn from(value: #name) -> #mojom_value {
+                match value {
+                    #(#name::#variant_idents => #mojom_value::Enum(#name::#variant_idents as i64)),*
+                }
+            }
+        }
+
+        impl TryFrom<#mojom_value> for #name {
             type Error = ::anyhow::Error;
 
-            fn try_from(value : MojomValue) -> ::anyhow::Result<Self> {
-                // FOR_RELEASE: Don't clone here
-                if let MojomValue::Struct(fields) = value.clone() {
-                    // Drop the strings, we don't care about them here
-                    let fields : Vec<MojomValue> = fields.into_iter().map(|field| field.1).collect();
-                    // Try to extract all the field values at once
-                    let fields : [MojomValue; #num_fields] = fields.try_into()
-                      .or(Err(::anyhow::anyhow!(
-                            "Wrong number of fields to construct a value of type {} from MojomValue {:?}",
-                                std::any::type_name::<#name>(),
-                                value)))?;
-                    let [#(#field_idents),*] = fields;
-                    return Ok(Self {
-                        #(#from_mojom_value_fields),*
-                    })
+            fn try_from(value: #mojom_value) -> ::anyhow::Result<Self> {
+                if let #mojom_value::Enum(discriminant) = value {
+                    #(if discriminant == (#name::#variant_idents as i64) {
+                        return Ok(#name::#variant_idents);
+                    })*
+                    ::anyhow::bail!(
+                        "Discriminant {} does not match any variant of {}",
+                        discriminant,
+                        std::any::type_name::<#name>(),
+                    );
                 } else {
                     ::anyhow::bail!(
-                        "Cannot construct a value of type {} from non-struct MojomValue {:?}",
+                        "Cannot construct a value of type {} from non-enum MojomValue {:?}",
                         std::any::type_name::<#name>(),
                         value
                     );
                 }
             }
         }
-    };
+    }
+}
 
-    // Excellent for debugging, prints out the entire generated code
-    // println!("{}", &quoted);
-    return proc_macro::TokenStream::from(quoted);
+fn derive_union_enum(
+    name: &syn::Ident,
+    data_enum: &syn::DataEnum,
+    paths: &MojomPaths,
+) -> proc_macro2::TokenStream {
+    let variant_idents: Vec<&syn::Ident> =
+        data_enum.variants.iter().map(|variant| &variant.ident).collect();
+    let variant_names: Vec<String> =
+        variant_idents.iter().map(|ident| ident.to_string()).collect();
+    let variant_tys: Vec<&syn::Type> = data_enum
+        .variants
+        .iter()
+        .map(|variant| match &variant.fields {
+            syn::Fields::Unnamed(fields) => &fields.unnamed.first().unwrap().ty,
+            _ => unreachable!("derive_union_enum only called when all variants are single-field"),
+        })
+        .collect();
+    let MojomPaths { mojom_parse, mojom_type, mojom_value } = paths;
+
+    quote! {
+        impl #mojom_parse for #name {
+            fn mojom_type() -> #mojom_type {
+                let variants : Vec<(String, #mojom_type)> = vec![
+                    #((#variant_names.to_string(), <#variant_tys as #mojom_parse>::mojom_type())),*
+                ];
+                #mojom_type::Union { variants }
+            }
+        }
+
+        impl From<#name> for #mojom_value {
+            fn from(value: #name) -> #mojom_value {
+                match value {
+                    #(#name::#variant_idents(inner) =>
+                        #mojom_value::Union(#variant_names.to_string(), Box::new(inner.into()))),*
+                }
+            }
+        }
+
+        impl TryFrom<#mojom_value> for #name {
+            type Error = ::anyhow::Error;
+
+            fn try_from(value: #mojom_value) -> ::anyhow::Result<Self> {
+                if let #mojom_value::Union(tag, inner) = value {
+                    match tag.as_str() {
+                        #(#variant_names => return Ok(#name::#variant_idents((*inner).try_into()?)),)*
+                        _ => ::anyhow::bail!(
+                            "Tag {:?} does not match any variant of union {}",
+                            tag,
+                            std::any::type_name::<#name>(),
+                        ),
+                    }
+                } else {
+                    ::anyhow::bail!(
+                        "Cannot construct a value of type {} from non-union MojomValue {:?}",
+                        std::any::type_name::<#name>(),
+                        value
+                    );
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,307 @@
+// Copyright 2025 The Chromium Authors
+// Use of this source code is governed by a BSD-style license that can be
+// found in the LICENSE file.
+
+//! Compile+round-trip tests for `#[derive(MojomParse)]`.
+//!
+//! In production, the types the generated code refers to by their bare
+//! names (`MojomParse`, `MojomType`, `MojomValue`) are brought into scope
+//! via `chromium::import!`, per the crate's own module docs. These tests run
+//! as an ordinary Rust integration test binary outside the GN/chromium
+//! build, so `rt` below stands in for that runtime: a minimal, real
+//! implementation of the same three names, just enough to let the generated
+//! `MojomParse`/`From`/`TryFrom` impls actually compile and run.
+
+mod rt {
+    #[derive(Debug, PartialEq)]
+    pub enum MojomType {
+        Struct { fields: Vec<(String, MojomType, Option<u32>)> },
+        Enum { variants: Vec<(String, i64)> },
+        Union { variants: Vec<(String, MojomType)> },
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum MojomValue {
+        Struct(Vec<(String, MojomValue)>),
+        Enum(i64),
+        Union(String, Box<MojomValue>),
+        I32(i32),
+        Str(String),
+    }
+
+    pub trait MojomParse {
+        fn mojom_type() -> MojomType;
+    }
+
+    impl MojomParse for i32 {
+        fn mojom_type() -> MojomType {
+            MojomType::Struct { fields: vec![] }
+        }
+    }
+    impl From<i32> for MojomValue {
+        fn from(value: i32) -> Self {
+            MojomValue::I32(value)
+        }
+    }
+    impl TryFrom<MojomValue> for i32 {
+        type Error = ::anyhow::Error;
+        fn try_from(value: MojomValue) -> ::anyhow::Result<Self> {
+            match value {
+                MojomValue::I32(value) => Ok(value),
+                other => ::anyhow::bail!("expected MojomValue::I32, got {:?}", other),
+            }
+        }
+    }
+
+    impl MojomParse for String {
+        fn mojom_type() -> MojomType {
+            MojomType::Struct { fields: vec![] }
+        }
+    }
+    impl From<String> for MojomValue {
+        fn from(value: String) -> Self {
+            MojomValue::Str(value)
+        }
+    }
+    impl TryFrom<MojomValue> for String {
+        type Error = ::anyhow::Error;
+        fn try_from(value: MojomValue) -> ::anyhow::Result<Self> {
+            match value {
+                MojomValue::Str(value) => Ok(value),
+                other => ::anyhow::bail!("expected MojomValue::Str, got {:?}", other),
+            }
+        }
+    }
+}
+
+mod plain_struct {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[test]
+    fn round_trips_through_mojom_value() {
+        let point = Point { x: 1, y: 2 };
+        let value: MojomValue = point.into();
+        let MojomValue::Struct(fields) = &value else { panic!("expected a struct value") };
+        assert_eq!(fields.len(), 2);
+
+        let round_tripped: Point = value.try_into().unwrap();
+        assert_eq!((round_tripped.x, round_tripped.y), (1, 2));
+    }
+
+    #[test]
+    fn mojom_type_lists_every_field() {
+        let MojomType::Struct { fields } = Point::mojom_type() else { panic!("expected a struct type") };
+        let names: Vec<&str> = fields.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["x", "y"]);
+    }
+}
+
+mod c_like_enum {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    enum Color {
+        Red = 3,
+        Green,
+        Blue,
+    }
+
+    #[test]
+    fn respects_explicit_discriminant_and_auto_increments() {
+        let MojomType::Enum { variants } = Color::mojom_type() else { panic!("expected an enum type") };
+        assert_eq!(
+            variants,
+            vec![
+                ("Red".to_string(), 3),
+                ("Green".to_string(), 4),
+                ("Blue".to_string(), 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips_through_mojom_value() {
+        let value: MojomValue = Color::Green.into();
+        assert_eq!(value, MojomValue::Enum(4));
+        let round_tripped: Color = value.try_into().unwrap();
+        assert!(matches!(round_tripped, Color::Green));
+    }
+
+    #[test]
+    fn rejects_an_unknown_discriminant() {
+        let result: Result<Color, _> = MojomValue::Enum(99).try_into();
+        assert!(result.is_err());
+    }
+}
+
+mod union_enum {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    enum Either {
+        Number(i32),
+        Text(String),
+    }
+
+    #[test]
+    fn round_trips_through_mojom_value() {
+        let value: MojomValue = Either::Text("hi".to_string()).into();
+        assert_eq!(value, MojomValue::Union("Text".to_string(), Box::new(MojomValue::Str("hi".to_string()))));
+
+        let round_tripped: Either = value.try_into().unwrap();
+        assert!(matches!(round_tripped, Either::Text(s) if s == "hi"));
+    }
+
+    #[test]
+    fn mojom_type_lists_every_variant() {
+        let MojomType::Union { variants } = Either::mojom_type() else { panic!("expected a union type") };
+        let names: Vec<&str> = variants.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["Number", "Text"]);
+    }
+}
+
+mod rename_skip_default {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    struct Widget {
+        #[mojom(rename = "widgetId")]
+        id: i32,
+        #[mojom(skip)]
+        cached_display_name: String,
+        #[mojom(default)]
+        priority: i32,
+    }
+
+    #[test]
+    fn rename_controls_the_wire_name_and_skip_is_omitted() {
+        let MojomType::Struct { fields } = Widget::mojom_type() else { panic!("expected a struct type") };
+        let names: Vec<&str> = fields.iter().map(|(name, _, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["widgetId", "priority"]);
+
+        let widget = Widget { id: 7, cached_display_name: "ignored".to_string(), priority: 1 };
+        let value: MojomValue = widget.into();
+        let MojomValue::Struct(entries) = &value else { panic!("expected a struct value") };
+        assert!(entries.iter().any(|(name, _)| name == "widgetId"));
+        assert!(!entries.iter().any(|(name, _)| name == "cached_display_name"));
+    }
+
+    #[test]
+    fn default_fills_in_a_missing_field() {
+        let value = MojomValue::Struct(vec![("widgetId".to_string(), MojomValue::I32(7))]);
+        let widget: Widget = value.try_into().unwrap();
+        assert_eq!((widget.id, widget.cached_display_name, widget.priority), (7, String::new(), 0));
+    }
+
+    #[test]
+    fn a_genuine_duplicate_key_is_rejected() {
+        let value = MojomValue::Struct(vec![
+            ("widgetId".to_string(), MojomValue::I32(1)),
+            ("widgetId".to_string(), MojomValue::I32(2)),
+        ]);
+        let result: Result<Widget, _> = value.try_into();
+        assert!(result.is_err());
+    }
+}
+
+mod generic_struct {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    struct Wrapper<T> {
+        inner: T,
+    }
+
+    #[test]
+    fn round_trips_and_reports_a_mojom_type_for_the_concrete_instantiation() {
+        let wrapper = Wrapper { inner: 42_i32 };
+        let value: MojomValue = wrapper.into();
+        let round_tripped: Wrapper<i32> = value.try_into().unwrap();
+        assert_eq!(round_tripped.inner, 42);
+
+        let MojomType::Struct { fields } = Wrapper::<i32>::mojom_type() else {
+            panic!("expected a struct type")
+        };
+        assert_eq!(fields[0].0, "inner");
+    }
+
+    #[test]
+    fn a_non_struct_mojom_value_is_rejected_with_a_qualified_type_name() {
+        let result: Result<Wrapper<i32>, _> = MojomValue::Enum(1).try_into();
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Wrapper"));
+    }
+}
+
+mod optional_and_min_version {
+    use super::rt::{MojomParse, MojomType, MojomValue};
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    struct Config {
+        name: String,
+        #[mojom(optional, min_version = 3)]
+        timeout_ms: Option<i32>,
+    }
+
+    #[test]
+    fn mojom_type_unwraps_the_option_and_reports_min_version() {
+        let MojomType::Struct { fields } = Config::mojom_type() else { panic!("expected a struct type") };
+        let (name, _, min_version) = &fields[1];
+        assert_eq!(name, "timeout_ms");
+        assert_eq!(*min_version, Some(3));
+    }
+
+    #[test]
+    fn an_absent_optional_field_resolves_to_none() {
+        let value = MojomValue::Struct(vec![("name".to_string(), MojomValue::Str("old-sender".to_string()))]);
+        let config: Config = value.try_into().unwrap();
+        assert_eq!(config.timeout_ms, None);
+    }
+
+    #[test]
+    fn a_present_optional_field_round_trips() {
+        let config = Config { name: "new-sender".to_string(), timeout_ms: Some(500) };
+        let value: MojomValue = config.into();
+        let round_tripped: Config = value.try_into().unwrap();
+        assert_eq!(round_tripped.timeout_ms, Some(500));
+    }
+}
+
+mod crate_path_qualified {
+    use mojom_parser::MojomParse;
+
+    #[derive(MojomParse)]
+    #[mojom(crate = crate::rt)]
+    struct Point3 {
+        x: i32,
+        y: i32,
+        z: i32,
+    }
+
+    #[test]
+    fn works_without_mojom_parse_mojom_type_mojom_value_in_scope() {
+        // Only needed here to call `Point3::mojom_type()` below -- the
+        // struct definition above never imports `MojomParse`/`MojomType`/
+        // `MojomValue`, which is exactly the point of `#[mojom(crate = ..)]`.
+        use crate::rt::{MojomParse, MojomType, MojomValue};
+
+        let MojomType::Struct { fields } = Point3::mojom_type() else { panic!("expected a struct type") };
+        assert_eq!(fields.len(), 3);
+
+        let value: MojomValue = Point3 { x: 1, y: 2, z: 3 }.into();
+        let round_tripped: Point3 = value.try_into().unwrap();
+        assert_eq!((round_tripped.x, round_tripped.y, round_tripped.z), (1, 2, 3));
+    }
+}